@@ -4,8 +4,39 @@
 // This attribute marks this module as an ink! smart contract
 #[ink::contract]
 mod nft_card_game {
+    use ink::prelude::format;
     use ink::storage::Mapping;
 
+    // Emitted when a new card is minted
+    #[ink(event)]
+    pub struct CardMinted {
+        #[ink(topic)]
+        token_id: u32,
+        #[ink(topic)]
+        owner: AccountId,
+    }
+
+    // Emitted when a card changes ownership
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        #[ink(topic)]
+        token_id: u32,
+    }
+
+    // Emitted when a game is played between two cards
+    #[ink(event)]
+    pub struct GamePlayed {
+        #[ink(topic)]
+        player1_card: u32,
+        #[ink(topic)]
+        player2_card: u32,
+        winner: Option<u32>,
+    }
+
     // Custom error types for the contract
     #[derive(scale::Decode, scale::Encode, Debug, PartialEq, Eq, Copy, Clone)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -14,8 +45,25 @@ mod nft_card_game {
         TokenNotFound,
         NotApproved,
         TokenAlreadyExists,
+        CollectionNotFound,
+        SupplyCapReached,
+        InsufficientGold,
+        AuctionNotFound,
+        InsufficientPayment,
+        TransferFailed,
     }
 
+    // Gold awarded to the winner of a game
+    const GOLD_PER_WIN: u64 = 10;
+    // Gold cost to level up a card
+    const LEVEL_UP_COST: u64 = 50;
+    // Stat gained per level up
+    const LEVEL_UP_BONUS: u32 = 10;
+    // Gold cost to craft a new card out of two existing ones
+    const CRAFT_COST: u64 = 100;
+    // Minimum blocks between two games that use the same card as player1, so gold can't be farmed in an unbounded loop
+    const PLAY_COOLDOWN_BLOCKS: BlockNumber = 10;
+
     // Struct representing a card in the game
     #[derive(scale::Decode, scale::Encode, Clone)]
     #[cfg_attr(
@@ -26,15 +74,69 @@ mod nft_card_game {
         name: String,
         attack: u32,
         defense: u32,
+        collection_id: u32,
+        item_id: u32,
+        wins: u32,
+    }
+
+    // Struct representing a collection (a set of cards minted by the same creator)
+    #[derive(scale::Decode, scale::Encode, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Collection {
+        name: String,
+        creator: AccountId,
+        next_item_id: u32,
+        max_supply: u32,
+    }
+
+    // Struct describing a Dutch auction listing for a single card
+    #[derive(scale::Decode, scale::Encode, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Auction {
+        seller: AccountId,
+        start_price: Balance,
+        end_price: Balance,
+        start_block: BlockNumber,
+        duration: BlockNumber,
     }
 
     // Main contract storage structure
     #[ink(storage)]
     pub struct NftCardGame {
-        owner: AccountId,
         cards: Mapping<u32, Card>,
         card_owners: Mapping<u32, AccountId>,
         next_token_id: u32,
+        // Per-token approval, cleared whenever the token moves
+        approvals: Mapping<u32, AccountId>,
+        // Operator approvals: (owner, operator) -> approved for all of owner's tokens
+        operator_approvals: Mapping<(AccountId, AccountId), ()>,
+        // Collections, each with their own creator and supply cap
+        collections: Mapping<u32, Collection>,
+        next_collection_id: u32,
+        // (collection_id, item_id) -> token_id, for lookups within a collection
+        collection_tokens: Mapping<(u32, u32), u32>,
+        // Fungible gold balance, earned from games and spent on progression
+        gold: Mapping<AccountId, u64>,
+        // Current renter and expiry block for each token, per EIP-4907
+        users: Mapping<u32, (AccountId, u64)>,
+        // Active Dutch-auction listings, keyed by token ID
+        auctions: Mapping<u32, Auction>,
+        // Number of cards owned by each account
+        balances: Mapping<AccountId, u32>,
+        // (owner, index) -> token ID, a dense per-owner enumeration of token IDs
+        owned_tokens: Mapping<(AccountId, u32), u32>,
+        // token ID -> its index within its owner's `owned_tokens` list
+        owned_tokens_index: Mapping<u32, u32>,
+        // Number of cards that currently exist (minted minus burned)
+        total_supply: u32,
+        // Last block a card was played as player1, to rate-limit gold farming
+        last_played: Mapping<u32, BlockNumber>,
     }
 
     impl NftCardGame {
@@ -42,33 +144,141 @@ mod nft_card_game {
         #[ink(constructor)]
         pub fn new() -> Self {
             Self {
-                owner: Self::env().caller(),
                 cards: Mapping::default(),
                 card_owners: Mapping::default(),
                 next_token_id: 1,
+                approvals: Mapping::default(),
+                operator_approvals: Mapping::default(),
+                collections: Mapping::default(),
+                next_collection_id: 1,
+                collection_tokens: Mapping::default(),
+                gold: Mapping::default(),
+                users: Mapping::default(),
+                auctions: Mapping::default(),
+                balances: Mapping::default(),
+                owned_tokens: Mapping::default(),
+                owned_tokens_index: Mapping::default(),
+                total_supply: 0,
+                last_played: Mapping::default(),
             }
         }
 
-        // Function to create a new card (only callable by the owner)
+        // Function to check how many cards an account owns
         #[ink(message)]
-        pub fn create_card(
+        pub fn balance_of(&self, owner: AccountId) -> u32 {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        // Function to look up a card ID by its position in an owner's token list
+        #[ink(message)]
+        pub fn token_of_owner_by_index(&self, owner: AccountId, index: u32) -> Option<u32> {
+            self.owned_tokens.get((owner, index))
+        }
+
+        // Function to return the number of cards that currently exist
+        #[ink(message)]
+        pub fn total_supply(&self) -> u32 {
+            self.total_supply
+        }
+
+        // Add a token to the dense end of an owner's enumeration list
+        fn add_to_owner(&mut self, owner: AccountId, token_id: u32) {
+            let index = self.balance_of(owner);
+            self.owned_tokens.insert((owner, index), &token_id);
+            self.owned_tokens_index.insert(token_id, &index);
+            self.balances.insert(owner, &(index + 1));
+        }
+
+        // Remove a token from an owner's enumeration list, moving the last token into its slot
+        fn remove_from_owner(&mut self, owner: AccountId, token_id: u32) {
+            let last_index = self.balance_of(owner) - 1;
+            let token_index = self.owned_tokens_index.get(token_id).unwrap_or(0);
+            if token_index != last_index {
+                let last_token_id = self
+                    .owned_tokens
+                    .get((owner, last_index))
+                    .expect("owner's token list is inconsistent");
+                self.owned_tokens.insert((owner, token_index), &last_token_id);
+                self.owned_tokens_index.insert(last_token_id, &token_index);
+            }
+            self.owned_tokens.remove((owner, last_index));
+            self.owned_tokens_index.remove(token_id);
+            self.balances.insert(owner, &last_index);
+        }
+
+        // Function to check an account's gold balance
+        #[ink(message)]
+        pub fn gold_of(&self, account: AccountId) -> u64 {
+            self.gold.get(account).unwrap_or(0)
+        }
+
+        // Function to create a new collection; the caller becomes its mint authority
+        #[ink(message)]
+        pub fn create_collection(&mut self, name: String, max_supply: u32) -> u32 {
+            let collection_id = self.next_collection_id;
+            self.next_collection_id += 1;
+            let collection = Collection {
+                name,
+                creator: self.env().caller(),
+                next_item_id: 1,
+                max_supply,
+            };
+            self.collections.insert(collection_id, &collection);
+            collection_id
+        }
+
+        // Function to retrieve a collection's details by its collection ID
+        #[ink(message)]
+        pub fn get_collection(&self, collection_id: u32) -> Option<Collection> {
+            self.collections.get(&collection_id)
+        }
+
+        // Function to look up a token ID by its position within a collection
+        #[ink(message)]
+        pub fn token_of_collection_item(&self, collection_id: u32, item_id: u32) -> Option<u32> {
+            self.collection_tokens.get((collection_id, item_id))
+        }
+
+        // Function to mint a new card into a collection (only callable by the collection's creator)
+        #[ink(message)]
+        pub fn mint(
             &mut self,
+            collection_id: u32,
             name: String,
             attack: u32,
             defense: u32,
         ) -> Result<u32, Error> {
-            if self.env().caller() != self.owner {
+            let mut collection = self
+                .collections
+                .get(&collection_id)
+                .ok_or(Error::CollectionNotFound)?;
+            if self.env().caller() != collection.creator {
                 return Err(Error::NotOwner);
             }
+            if collection.next_item_id > collection.max_supply {
+                return Err(Error::SupplyCapReached);
+            }
+            let item_id = collection.next_item_id;
+            collection.next_item_id += 1;
+            self.collections.insert(collection_id, &collection);
+
             let token_id = self.next_token_id;
             self.next_token_id += 1;
             let card = Card {
                 name,
                 attack,
                 defense,
+                collection_id,
+                item_id,
+                wins: 0,
             };
             self.cards.insert(token_id, &card);
-            self.card_owners.insert(token_id, &self.owner);
+            let owner = self.env().caller();
+            self.card_owners.insert(token_id, &owner);
+            self.add_to_owner(owner, token_id);
+            self.collection_tokens.insert((collection_id, item_id), &token_id);
+            self.total_supply += 1;
+            self.env().emit_event(CardMinted { token_id, owner });
             Ok(token_id)
         }
 
@@ -78,6 +288,45 @@ mod nft_card_game {
             self.cards.get(&token_id)
         }
 
+        // Function to approve a single account to transfer a specific card
+        #[ink(message)]
+        pub fn approve(&mut self, to: AccountId, token_id: u32) -> Result<(), Error> {
+            let owner = self
+                .card_owners
+                .get(&token_id)
+                .ok_or(Error::TokenNotFound)?;
+            let caller = self.env().caller();
+            let is_operator = self.operator_approvals.contains((owner, caller));
+            if owner != caller && !is_operator {
+                return Err(Error::NotApproved);
+            }
+            self.approvals.insert(token_id, &to);
+            Ok(())
+        }
+
+        // Function to approve or revoke an operator for all of the caller's cards
+        #[ink(message)]
+        pub fn set_approval_for_all(&mut self, operator: AccountId, approved: bool) {
+            let caller = self.env().caller();
+            if approved {
+                self.operator_approvals.insert((caller, operator), &());
+            } else {
+                self.operator_approvals.remove((caller, operator));
+            }
+        }
+
+        // Function to look up the account currently approved for a single card
+        #[ink(message)]
+        pub fn get_approved(&self, token_id: u32) -> Option<AccountId> {
+            self.approvals.get(&token_id)
+        }
+
+        // Function to check whether an operator is approved for all of owner's cards
+        #[ink(message)]
+        pub fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
+            self.operator_approvals.contains((owner, operator))
+        }
+
         // Function to transfer ownership of a card
         #[ink(message)]
         pub fn transfer(&mut self, to: AccountId, token_id: u32) -> Result<(), Error> {
@@ -85,27 +334,281 @@ mod nft_card_game {
                 .card_owners
                 .get(&token_id)
                 .ok_or(Error::TokenNotFound)?;
-            if owner != self.env().caller() {
+            let caller = self.env().caller();
+            let is_approved = self.approvals.get(&token_id).as_ref() == Some(&caller);
+            let is_operator = self.operator_approvals.contains((owner, caller));
+            if owner != caller && !is_approved && !is_operator {
                 return Err(Error::NotApproved);
             }
+            self.approvals.remove(token_id);
             self.card_owners.insert(token_id, &to);
+            self.remove_from_owner(owner, token_id);
+            self.add_to_owner(to, token_id);
+            self.env().emit_event(Transfer {
+                from: owner,
+                to,
+                token_id,
+            });
+            Ok(())
+        }
+
+        // Function to set the current renter and expiry block for a card (owner or approved only)
+        #[ink(message)]
+        pub fn set_user(&mut self, token_id: u32, user: AccountId, expires: u64) -> Result<(), Error> {
+            let owner = self
+                .card_owners
+                .get(&token_id)
+                .ok_or(Error::TokenNotFound)?;
+            let caller = self.env().caller();
+            let is_approved = self.approvals.get(&token_id).as_ref() == Some(&caller);
+            let is_operator = self.operator_approvals.contains((owner, caller));
+            if owner != caller && !is_approved && !is_operator {
+                return Err(Error::NotApproved);
+            }
+            self.users.insert(token_id, &(user, expires));
+            Ok(())
+        }
+
+        // Function to look up a card's current renter, if their rental has not expired
+        #[ink(message)]
+        pub fn user_of(&self, token_id: u32) -> Option<AccountId> {
+            let (user, expires) = self.users.get(&token_id)?;
+            if self.env().block_number() as u64 <= expires {
+                Some(user)
+            } else {
+                None
+            }
+        }
+
+        // Whether an account controls a card, as its owner or its active renter
+        fn controls(&self, token_id: u32, account: AccountId) -> bool {
+            if self.card_owners.get(&token_id) == Some(account) {
+                return true;
+            }
+            self.user_of(token_id) == Some(account)
+        }
+
+        // Function to list a card for sale in a Dutch auction (owner-only, escrows the card)
+        #[ink(message)]
+        pub fn list_for_auction(
+            &mut self,
+            token_id: u32,
+            start_price: Balance,
+            end_price: Balance,
+            duration: BlockNumber,
+        ) -> Result<(), Error> {
+            let owner = self
+                .card_owners
+                .get(&token_id)
+                .ok_or(Error::TokenNotFound)?;
+            let caller = self.env().caller();
+            if owner != caller {
+                return Err(Error::NotOwner);
+            }
+            let auction = Auction {
+                seller: caller,
+                start_price,
+                end_price,
+                start_block: self.env().block_number(),
+                duration,
+            };
+            self.auctions.insert(token_id, &auction);
+            self.approvals.remove(token_id);
+            let escrow = self.env().account_id();
+            self.card_owners.insert(token_id, &escrow);
+            self.remove_from_owner(caller, token_id);
+            self.add_to_owner(escrow, token_id);
             Ok(())
         }
 
-        // Function to simulate a game between two cards
+        // Function to read a listed card's current Dutch-auction price
         #[ink(message)]
-        pub fn play_game(&self, player1_card: u32, player2_card: u32) -> Option<u32> {
-            let card1 = self.get_card(player1_card)?;
-            let card2 = self.get_card(player2_card)?;
+        pub fn current_price(&self, token_id: u32) -> Option<Balance> {
+            let auction = self.auctions.get(&token_id)?;
+            let elapsed = self.env().block_number().saturating_sub(auction.start_block);
+            if elapsed >= auction.duration || auction.duration == 0 {
+                return Some(auction.end_price);
+            }
+            let price_drop = auction.start_price.saturating_sub(auction.end_price);
+            let decayed = price_drop * elapsed as Balance / auction.duration as Balance;
+            Some(auction.start_price.saturating_sub(decayed))
+        }
+
+        // Function to buy a listed card at its current Dutch-auction price
+        #[ink(message, payable)]
+        pub fn buy(&mut self, token_id: u32) -> Result<(), Error> {
+            let auction = self
+                .auctions
+                .get(&token_id)
+                .ok_or(Error::AuctionNotFound)?;
+            let price = self
+                .current_price(token_id)
+                .ok_or(Error::AuctionNotFound)?;
+            let paid = self.env().transferred_value();
+            if paid < price {
+                return Err(Error::InsufficientPayment);
+            }
+            let buyer = self.env().caller();
+            let escrow = self.env().account_id();
+
+            if paid > price {
+                self.env()
+                    .transfer(buyer, paid - price)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+            self.env()
+                .transfer(auction.seller, price)
+                .map_err(|_| Error::TransferFailed)?;
+
+            self.auctions.remove(token_id);
+            self.card_owners.insert(token_id, &buyer);
+            self.remove_from_owner(escrow, token_id);
+            self.add_to_owner(buyer, token_id);
+
+            self.env().emit_event(Transfer {
+                from: auction.seller,
+                to: buyer,
+                token_id,
+            });
+            Ok(())
+        }
+
+        // Function to play a game between two cards, awarding gold and a win to the winner
+        #[ink(message)]
+        pub fn play_game(&mut self, player1_card: u32, player2_card: u32) -> Option<u32> {
+            let caller = self.env().caller();
+            if !self.controls(player1_card, caller) {
+                return None;
+            }
+            let now = self.env().block_number();
+            if let Some(last_played) = self.last_played.get(player1_card) {
+                if now.saturating_sub(last_played) < PLAY_COOLDOWN_BLOCKS {
+                    return None;
+                }
+            }
+            self.last_played.insert(player1_card, &now);
+
+            let mut card1 = self.get_card(player1_card)?;
+            let mut card2 = self.get_card(player2_card)?;
             let player1_power = card1.attack + card1.defense;
             let player2_power = card2.attack + card2.defense;
-            if player1_power > player2_power {
+            let winner = if player1_power > player2_power {
                 Some(player1_card)
             } else if player2_power > player1_power {
                 Some(player2_card)
             } else {
                 None // It's a tie
+            };
+
+            if let Some(winner) = winner {
+                if winner == player1_card {
+                    card1.wins += 1;
+                    self.cards.insert(player1_card, &card1);
+                } else {
+                    card2.wins += 1;
+                    self.cards.insert(player2_card, &card2);
+                }
+
+                if let Some(owner) = self.card_owners.get(winner) {
+                    let balance = self.gold_of(owner);
+                    self.gold.insert(owner, &(balance + GOLD_PER_WIN));
+                }
+            }
+
+            self.env().emit_event(GamePlayed {
+                player1_card,
+                player2_card,
+                winner,
+            });
+            winner
+        }
+
+        // Function to burn gold and raise a card's attack and defense
+        #[ink(message)]
+        pub fn level_up(&mut self, token_id: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let owner = self
+                .card_owners
+                .get(&token_id)
+                .ok_or(Error::TokenNotFound)?;
+            if owner != caller {
+                return Err(Error::NotOwner);
+            }
+            let balance = self.gold_of(caller);
+            if balance < LEVEL_UP_COST {
+                return Err(Error::InsufficientGold);
+            }
+            self.gold.insert(caller, &(balance - LEVEL_UP_COST));
+
+            let mut card = self.cards.get(&token_id).ok_or(Error::TokenNotFound)?;
+            card.attack += LEVEL_UP_BONUS;
+            card.defense += LEVEL_UP_BONUS;
+            self.cards.insert(token_id, &card);
+            Ok(())
+        }
+
+        // Function to burn two owned cards and gold to mint a stronger card
+        #[ink(message)]
+        pub fn craft(&mut self, card_a: u32, card_b: u32) -> Result<u32, Error> {
+            if card_a == card_b {
+                return Err(Error::TokenNotFound);
+            }
+            let caller = self.env().caller();
+            let owner_a = self.card_owners.get(&card_a).ok_or(Error::TokenNotFound)?;
+            let owner_b = self.card_owners.get(&card_b).ok_or(Error::TokenNotFound)?;
+            if owner_a != caller || owner_b != caller {
+                return Err(Error::NotOwner);
+            }
+            let balance = self.gold_of(caller);
+            if balance < CRAFT_COST {
+                return Err(Error::InsufficientGold);
             }
+            let a = self.cards.get(&card_a).ok_or(Error::TokenNotFound)?;
+            let b = self.cards.get(&card_b).ok_or(Error::TokenNotFound)?;
+
+            let mut collection = self
+                .collections
+                .get(&a.collection_id)
+                .ok_or(Error::CollectionNotFound)?;
+            if collection.next_item_id > collection.max_supply {
+                return Err(Error::SupplyCapReached);
+            }
+            let item_id = collection.next_item_id;
+            collection.next_item_id += 1;
+            self.collections.insert(a.collection_id, &collection);
+
+            self.gold.insert(caller, &(balance - CRAFT_COST));
+            self.cards.remove(card_a);
+            self.cards.remove(card_b);
+            self.card_owners.remove(card_a);
+            self.card_owners.remove(card_b);
+            self.approvals.remove(card_a);
+            self.approvals.remove(card_b);
+            self.remove_from_owner(caller, card_a);
+            self.remove_from_owner(caller, card_b);
+            self.total_supply -= 2;
+
+            let token_id = self.next_token_id;
+            self.next_token_id += 1;
+            let crafted = Card {
+                name: format!("{}+{}", a.name, b.name),
+                attack: a.attack + b.attack,
+                defense: a.defense + b.defense,
+                collection_id: a.collection_id,
+                item_id,
+                wins: 0,
+            };
+            self.cards.insert(token_id, &crafted);
+            self.card_owners.insert(token_id, &caller);
+            self.add_to_owner(caller, token_id);
+            self.collection_tokens
+                .insert((a.collection_id, item_id), &token_id);
+            self.total_supply += 1;
+            self.env().emit_event(CardMinted {
+                token_id,
+                owner: caller,
+            });
+            Ok(token_id)
         }
     }
 
@@ -114,26 +617,374 @@ mod nft_card_game {
     mod tests {
         use super::*;
 
-        // Test the card creation functionality
+        // Test the collection creation and minting functionality
         #[ink::test]
-        fn create_card_works() {
+        fn mint_works() {
             let mut nft_game = NftCardGame::new();
-            let token_id = nft_game.create_card("Dragon".to_string(), 100, 50).unwrap();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
             assert_eq!(token_id, 1);
             let card = nft_game.get_card(token_id).unwrap();
             assert_eq!(card.name, "Dragon");
             assert_eq!(card.attack, 100);
             assert_eq!(card.defense, 50);
+            assert_eq!(
+                nft_game.token_of_collection_item(collection_id, card.item_id),
+                Some(token_id)
+            );
+        }
+
+        // Test that minting past a collection's max supply is rejected
+        #[ink::test]
+        fn mint_enforces_supply_cap() {
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 1);
+            nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            assert_eq!(
+                nft_game.mint(collection_id, "Knight".to_string(), 80, 60),
+                Err(Error::SupplyCapReached)
+            );
+        }
+
+        // Test that only a collection's creator can mint into it
+        #[ink::test]
+        fn mint_requires_collection_creator() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                nft_game.mint(collection_id, "Dragon".to_string(), 100, 50),
+                Err(Error::NotOwner)
+            );
         }
 
         // Test the game playing functionality
         #[ink::test]
         fn play_game_works() {
             let mut nft_game = NftCardGame::new();
-            let token_id1 = nft_game.create_card("Dragon".to_string(), 100, 50).unwrap();
-            let token_id2 = nft_game.create_card("Knight".to_string(), 80, 60).unwrap();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 80, 60)
+                .unwrap();
             let winner = nft_game.play_game(token_id1, token_id2).unwrap();
             assert_eq!(winner, token_id1);
         }
+
+        // Test that an approved account can transfer a card it does not own
+        #[ink::test]
+        fn approved_account_can_transfer() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+
+            nft_game.approve(accounts.bob, token_id).unwrap();
+            assert_eq!(nft_game.get_approved(token_id), Some(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            nft_game.transfer(accounts.charlie, token_id).unwrap();
+            assert_eq!(nft_game.get_approved(token_id), None);
+        }
+
+        // Test that an operator approved for all cards can transfer on the owner's behalf
+        #[ink::test]
+        fn operator_can_transfer() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+
+            nft_game.set_approval_for_all(accounts.bob, true);
+            assert!(nft_game.is_approved_for_all(accounts.alice, accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            nft_game.transfer(accounts.charlie, token_id).unwrap();
+        }
+
+        // Test that an operator approved for all cards can also set a single-token approval
+        #[ink::test]
+        fn operator_can_approve_on_owners_behalf() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+
+            nft_game.set_approval_for_all(accounts.bob, true);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            nft_game.approve(accounts.charlie, token_id).unwrap();
+            assert_eq!(nft_game.get_approved(token_id), Some(accounts.charlie));
+        }
+
+        // Test that an unapproved account cannot transfer a card
+        #[ink::test]
+        fn unapproved_account_cannot_transfer() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                nft_game.transfer(accounts.charlie, token_id),
+                Err(Error::NotApproved)
+            );
+        }
+
+        // Test that playing a game awards gold and a win to the winning card's owner
+        #[ink::test]
+        fn play_game_awards_gold() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 80, 60)
+                .unwrap();
+
+            let winner = nft_game.play_game(token_id1, token_id2).unwrap();
+            assert_eq!(winner, token_id1);
+            assert_eq!(nft_game.gold_of(accounts.alice), GOLD_PER_WIN);
+            assert_eq!(nft_game.get_card(token_id1).unwrap().wins, 1);
+        }
+
+        // Test that leveling up burns gold and raises attack and defense
+        #[ink::test]
+        fn level_up_works() {
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 1, 1)
+                .unwrap();
+            // Win enough games to afford a level up, advancing past the per-card cooldown each time
+            for i in 0..(LEVEL_UP_COST / GOLD_PER_WIN) {
+                ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(
+                    (i as u32 + 1) * PLAY_COOLDOWN_BLOCKS,
+                );
+                nft_game.play_game(token_id1, token_id2).unwrap();
+            }
+
+            nft_game.level_up(token_id1).unwrap();
+            let card = nft_game.get_card(token_id1).unwrap();
+            assert_eq!(card.attack, 100 + LEVEL_UP_BONUS);
+            assert_eq!(card.defense, 50 + LEVEL_UP_BONUS);
+        }
+
+        // Test that leveling up without enough gold fails
+        #[ink::test]
+        fn level_up_requires_gold() {
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            assert_eq!(nft_game.level_up(token_id), Err(Error::InsufficientGold));
+        }
+
+        // Test that crafting burns both cards and gold to mint a stronger one
+        #[ink::test]
+        fn craft_works() {
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 1, 1)
+                .unwrap();
+            for i in 0..(CRAFT_COST / GOLD_PER_WIN) {
+                ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(
+                    (i as u32 + 1) * PLAY_COOLDOWN_BLOCKS,
+                );
+                nft_game.play_game(token_id1, token_id2).unwrap();
+            }
+
+            let crafted_id = nft_game.craft(token_id1, token_id2).unwrap();
+            assert!(nft_game.get_card(token_id1).is_none());
+            assert!(nft_game.get_card(token_id2).is_none());
+            assert_eq!(nft_game.total_supply(), 1);
+            let crafted = nft_game.get_card(crafted_id).unwrap();
+            assert_eq!(crafted.attack, 101);
+            assert_eq!(crafted.defense, 51);
+        }
+
+        // Test that crafting a card with itself is rejected
+        #[ink::test]
+        fn craft_rejects_self_craft() {
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 1, 1)
+                .unwrap();
+            for i in 0..(CRAFT_COST / GOLD_PER_WIN) {
+                ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(
+                    (i as u32 + 1) * PLAY_COOLDOWN_BLOCKS,
+                );
+                nft_game.play_game(token_id1, token_id2).unwrap();
+            }
+            assert_eq!(
+                nft_game.craft(token_id1, token_id1),
+                Err(Error::TokenNotFound)
+            );
+        }
+
+        // Test that a renter can play a card until their rental expires
+        #[ink::test]
+        fn renter_can_play_until_expiry() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 80, 60)
+                .unwrap();
+
+            nft_game.set_user(token_id1, accounts.bob, 10).unwrap();
+            assert_eq!(nft_game.user_of(token_id1), Some(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let winner = nft_game.play_game(token_id1, token_id2).unwrap();
+            assert_eq!(winner, token_id1);
+
+            // Past the expiry block the renter no longer controls the card
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(11);
+            assert_eq!(nft_game.user_of(token_id1), None);
+            assert_eq!(nft_game.play_game(token_id1, token_id2), None);
+        }
+
+        // Test that a card cannot be played by an account with no rental or ownership
+        #[ink::test]
+        fn non_renter_cannot_play() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 80, 60)
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_game.play_game(token_id1, token_id2), None);
+        }
+
+        // Test that the Dutch-auction price decays linearly from start to end price
+        #[ink::test]
+        fn current_price_decays_linearly() {
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+
+            nft_game.list_for_auction(token_id, 100, 0, 10).unwrap();
+            assert_eq!(nft_game.current_price(token_id), Some(100));
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(5);
+            assert_eq!(nft_game.current_price(token_id), Some(50));
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(10);
+            assert_eq!(nft_game.current_price(token_id), Some(0));
+        }
+
+        // Test that buying a listed card at its current price closes the auction and transfers ownership
+        #[ink::test]
+        fn buy_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            nft_game.list_for_auction(token_id, 100, 0, 10).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            nft_game.buy(token_id).unwrap();
+
+            assert_eq!(nft_game.current_price(token_id), None);
+            assert_eq!(nft_game.balance_of(accounts.bob), 1);
+            assert_eq!(nft_game.token_of_owner_by_index(accounts.bob, 0), Some(token_id));
+        }
+
+        // Test that buying below the current price is rejected
+        #[ink::test]
+        fn buy_rejects_underpayment() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            nft_game.list_for_auction(token_id, 100, 0, 10).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(10);
+            assert_eq!(nft_game.buy(token_id), Err(Error::InsufficientPayment));
+        }
+
+        // Test that balance and enumeration stay consistent across mint and transfer
+        #[ink::test]
+        fn enumeration_stays_dense_across_transfer() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut nft_game = NftCardGame::new();
+            let collection_id = nft_game.create_collection("Genesis".to_string(), 10);
+            let token_id1 = nft_game
+                .mint(collection_id, "Dragon".to_string(), 100, 50)
+                .unwrap();
+            let token_id2 = nft_game
+                .mint(collection_id, "Knight".to_string(), 80, 60)
+                .unwrap();
+            let token_id3 = nft_game
+                .mint(collection_id, "Mage".to_string(), 70, 40)
+                .unwrap();
+            assert_eq!(nft_game.balance_of(accounts.alice), 3);
+            assert_eq!(nft_game.total_supply(), 3);
+
+            // Transfer the first-minted card away and check the last token fills its slot
+            nft_game.transfer(accounts.bob, token_id1).unwrap();
+            assert_eq!(nft_game.balance_of(accounts.alice), 2);
+            assert_eq!(nft_game.balance_of(accounts.bob), 1);
+            assert_eq!(
+                nft_game.token_of_owner_by_index(accounts.alice, 0),
+                Some(token_id3)
+            );
+            assert_eq!(
+                nft_game.token_of_owner_by_index(accounts.alice, 1),
+                Some(token_id2)
+            );
+            assert_eq!(
+                nft_game.token_of_owner_by_index(accounts.bob, 0),
+                Some(token_id1)
+            );
+        }
     }
 }